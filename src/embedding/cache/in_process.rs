@@ -0,0 +1,96 @@
+//! In-process `CacheBackend` implementation, suitable for a single worker or
+//! as the L1 tier in front of [`super::memcached`].
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+
+use crate::MemResult;
+
+use super::CacheBackend;
+
+struct Entry {
+    value: Vec<u8>,
+    expires_at: Instant,
+}
+
+/// A `HashMap`-backed cache with per-entry TTL, guarded by a single mutex.
+pub struct InProcessCache {
+    entries: Mutex<HashMap<u64, Entry>>,
+}
+
+impl InProcessCache {
+    /// Create an empty cache.
+    pub fn new() -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl Default for InProcessCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl CacheBackend for InProcessCache {
+    async fn get(&self, key: u64) -> MemResult<Option<Vec<u8>>> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(&key) {
+            Some(entry) if entry.expires_at > Instant::now() => Ok(Some(entry.value.clone())),
+            Some(_) => {
+                entries.remove(&key);
+                Ok(None)
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn set(&self, key: u64, value: Vec<u8>, ttl: Duration) -> MemResult<()> {
+        self.entries.lock().unwrap().insert(
+            key,
+            Entry {
+                value,
+                expires_at: Instant::now() + ttl,
+            },
+        );
+        Ok(())
+    }
+
+    async fn delete(&self, key: u64) -> MemResult<()> {
+        self.entries.lock().unwrap().remove(&key);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn set_then_get_roundtrips() {
+        let cache = InProcessCache::new();
+        cache.set(1, vec![1, 2, 3], Duration::from_secs(60)).await.unwrap();
+        assert_eq!(cache.get(1).await.unwrap(), Some(vec![1, 2, 3]));
+    }
+
+    #[tokio::test]
+    async fn expired_entries_are_absent() {
+        let cache = InProcessCache::new();
+        cache.set(1, vec![1], Duration::from_millis(0)).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        assert_eq!(cache.get(1).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn delete_removes_entry() {
+        let cache = InProcessCache::new();
+        cache.set(1, vec![1], Duration::from_secs(60)).await.unwrap();
+        cache.delete(1).await.unwrap();
+        assert_eq!(cache.get(1).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,480 @@
+//! Memcached binary-protocol client and server.
+//!
+//! Implements the subset of the [memcached binary protocol][proto] needed
+//! for a cache tier: `Get`/`GetQ`, `Set`/`SetQ`, `Add`, `Replace`, `Delete`,
+//! `Flush`, `Noop`, and `Quit`. Values are looked up by our own `u64` cache
+//! keys (see [`super::embedding_cache_key`] and [`super::retrieval_cache_key`])
+//! encoded as the wire key, so any memcached-compatible client or tooling
+//! can talk to [`MemcachedServer`] directly.
+//!
+//! [proto]: https://github.com/memcached/memcached/blob/master/doc/protocol-binary.xml
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Mutex;
+
+use crate::{MemError, MemResult};
+
+use super::CacheBackend;
+
+const REQUEST_MAGIC: u8 = 0x80;
+const RESPONSE_MAGIC: u8 = 0x81;
+const HEADER_LEN: usize = 24;
+
+/// Upper bound on a single request's body (extras + key + value). Generous
+/// for cached embeddings and ranked hit lists, while keeping one malformed
+/// or malicious `total_body_len` from forcing a multi-gigabyte allocation.
+const MAX_BODY_LEN: u32 = 16 * 1024 * 1024;
+
+/// Opcodes from the memcached binary protocol that this cache supports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Opcode {
+    /// Fetch a value; errors if absent.
+    Get = 0x00,
+    /// Store a value unconditionally.
+    Set = 0x01,
+    /// Store a value only if the key is absent.
+    Add = 0x02,
+    /// Store a value only if the key is present.
+    Replace = 0x03,
+    /// Remove a value.
+    Delete = 0x04,
+    /// No-op, used to flush pipelined quiet responses.
+    Noop = 0x0a,
+    /// Evict all entries.
+    Flush = 0x08,
+    /// Close the connection.
+    Quit = 0x07,
+    /// Quiet `Get`: no response on miss.
+    GetQ = 0x09,
+    /// Quiet `Set`: no response on success.
+    SetQ = 0x11,
+}
+
+impl Opcode {
+    fn from_u8(b: u8) -> MemResult<Self> {
+        Ok(match b {
+            0x00 => Opcode::Get,
+            0x01 => Opcode::Set,
+            0x02 => Opcode::Add,
+            0x03 => Opcode::Replace,
+            0x04 => Opcode::Delete,
+            0x0a => Opcode::Noop,
+            0x08 => Opcode::Flush,
+            0x07 => Opcode::Quit,
+            0x09 => Opcode::GetQ,
+            0x11 => Opcode::SetQ,
+            other => return Err(MemError::InvalidLayout(format!("unsupported opcode {other:#x}"))),
+        })
+    }
+}
+
+/// Status codes returned in response headers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u16)]
+pub enum Status {
+    /// Request succeeded.
+    Ok = 0x0000,
+    /// `Get`/`Replace`/`Delete` target did not exist.
+    KeyNotFound = 0x0001,
+    /// `Add` target already existed.
+    KeyExists = 0x0002,
+}
+
+struct PacketHeader {
+    magic: u8,
+    opcode: u8,
+    key_len: u16,
+    extras_len: u8,
+    status_or_vbucket: u16,
+    total_body_len: u32,
+    opaque: u32,
+    cas: u64,
+}
+
+impl PacketHeader {
+    fn parse(bytes: &[u8; HEADER_LEN]) -> Self {
+        Self {
+            magic: bytes[0],
+            opcode: bytes[1],
+            key_len: u16::from_be_bytes([bytes[2], bytes[3]]),
+            extras_len: bytes[4],
+            status_or_vbucket: u16::from_be_bytes([bytes[6], bytes[7]]),
+            total_body_len: u32::from_be_bytes(bytes[8..12].try_into().unwrap()),
+            opaque: u32::from_be_bytes(bytes[12..16].try_into().unwrap()),
+            cas: u64::from_be_bytes(bytes[16..24].try_into().unwrap()),
+        }
+    }
+
+    /// Validate that `extras_len + key_len` fits within `total_body_len` and
+    /// that `total_body_len` itself is within [`MAX_BODY_LEN`], so callers
+    /// can safely allocate a body buffer and slice it by those lengths.
+    /// Applies to both request headers (server-side) and response headers
+    /// (client-side) - the wire format is the same in both directions, and
+    /// either end may be talking to a buggy or malicious peer.
+    fn check_body_bounds(&self) -> MemResult<()> {
+        if self.total_body_len > MAX_BODY_LEN {
+            return Err(MemError::InvalidLayout(format!(
+                "total_body_len {} exceeds max {MAX_BODY_LEN}",
+                self.total_body_len
+            )));
+        }
+        let prefix_len = self.extras_len as u32 + self.key_len as u32;
+        if prefix_len > self.total_body_len {
+            return Err(MemError::InvalidLayout(format!(
+                "extras_len ({}) + key_len ({}) exceeds total_body_len ({})",
+                self.extras_len, self.key_len, self.total_body_len
+            )));
+        }
+        Ok(())
+    }
+
+    fn response(opcode: u8, status: Status, extras_len: u8, key_len: u16, value_len: u32, opaque: u32) -> [u8; HEADER_LEN] {
+        let mut out = [0u8; HEADER_LEN];
+        out[0] = RESPONSE_MAGIC;
+        out[1] = opcode;
+        out[2..4].copy_from_slice(&key_len.to_be_bytes());
+        out[4] = extras_len;
+        out[6..8].copy_from_slice(&(status as u16).to_be_bytes());
+        let total_body_len = extras_len as u32 + key_len as u32 + value_len;
+        out[8..12].copy_from_slice(&total_body_len.to_be_bytes());
+        out[12..16].copy_from_slice(&opaque.to_be_bytes());
+        out
+    }
+}
+
+struct Entry {
+    value: Vec<u8>,
+    expires_at: Instant,
+}
+
+type Store = Mutex<HashMap<Vec<u8>, Entry>>;
+
+/// A memcached binary-protocol server backed by an in-memory store, so a
+/// fleet of ReasonKit workers can point at one shared cache instance.
+pub struct MemcachedServer {
+    store: Arc<Store>,
+}
+
+impl MemcachedServer {
+    /// Create a server with an empty store.
+    pub fn new() -> Self {
+        Self {
+            store: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Serve connections on an already bound `listener` until accepting a
+    /// connection fails or the returned future is dropped. Each connection is
+    /// handled on its own task.
+    pub async fn serve(self: Arc<Self>, listener: TcpListener) -> MemResult<()> {
+        loop {
+            let (socket, _) = listener.accept().await?;
+            let store = self.store.clone();
+            tokio::spawn(async move {
+                let _ = handle_connection(socket, store).await;
+            });
+        }
+    }
+}
+
+impl Default for MemcachedServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+async fn handle_connection(mut socket: TcpStream, store: Arc<Store>) -> MemResult<()> {
+    loop {
+        let mut header_buf = [0u8; HEADER_LEN];
+        if socket.read_exact(&mut header_buf).await.is_err() {
+            return Ok(()); // peer closed the connection
+        }
+        let header = PacketHeader::parse(&header_buf);
+        if header.magic != REQUEST_MAGIC {
+            return Err(MemError::InvalidLayout("bad request magic".into()));
+        }
+        header.check_body_bounds()?;
+
+        let mut body = vec![0u8; header.total_body_len as usize];
+        socket.read_exact(&mut body).await?;
+        let extras = &body[..header.extras_len as usize];
+        let key = &body[header.extras_len as usize..header.extras_len as usize + header.key_len as usize];
+        let value = &body[header.extras_len as usize + header.key_len as usize..];
+
+        let opcode = Opcode::from_u8(header.opcode)?;
+        match opcode {
+            Opcode::Quit => return Ok(()),
+            Opcode::Noop => {
+                write_response(&mut socket, header.opcode, Status::Ok, &[], header.opaque).await?;
+            }
+            Opcode::Flush => {
+                store.lock().await.clear();
+                write_response(&mut socket, header.opcode, Status::Ok, &[], header.opaque).await?;
+            }
+            Opcode::Get | Opcode::GetQ => {
+                let found = {
+                    let mut store = store.lock().await;
+                    match store.get(key) {
+                        Some(entry) if entry.expires_at > Instant::now() => Some(entry.value.clone()),
+                        Some(_) => {
+                            store.remove(key);
+                            None
+                        }
+                        None => None,
+                    }
+                };
+                match found {
+                    Some(value) => write_get_response(&mut socket, header.opcode, &value, header.opaque).await?,
+                    None if opcode == Opcode::Get => {
+                        write_response(&mut socket, header.opcode, Status::KeyNotFound, &[], header.opaque).await?
+                    }
+                    None => {} // GetQ: silent on miss
+                }
+            }
+            Opcode::Set | Opcode::SetQ | Opcode::Add | Opcode::Replace => {
+                // Extras for Set/Add/Replace: 4-byte flags, 4-byte TTL (seconds).
+                let ttl_secs = if extras.len() >= 8 {
+                    u32::from_be_bytes(extras[4..8].try_into().unwrap())
+                } else {
+                    0
+                };
+                let mut store = store.lock().await;
+                let exists = store.contains_key(key);
+                let status = match opcode {
+                    Opcode::Add if exists => Some(Status::KeyExists),
+                    Opcode::Replace if !exists => Some(Status::KeyNotFound),
+                    _ => None,
+                };
+                if let Some(status) = status {
+                    write_response(&mut socket, header.opcode, status, &[], header.opaque).await?;
+                    continue;
+                }
+                store.insert(
+                    key.to_vec(),
+                    Entry {
+                        value: value.to_vec(),
+                        expires_at: Instant::now() + Duration::from_secs(ttl_secs as u64),
+                    },
+                );
+                if opcode != Opcode::SetQ {
+                    write_response(&mut socket, header.opcode, Status::Ok, &[], header.opaque).await?;
+                }
+            }
+            Opcode::Delete => {
+                let existed = store.lock().await.remove(key).is_some();
+                let status = if existed { Status::Ok } else { Status::KeyNotFound };
+                write_response(&mut socket, header.opcode, status, &[], header.opaque).await?;
+            }
+        }
+    }
+}
+
+async fn write_response(socket: &mut TcpStream, opcode: u8, status: Status, value: &[u8], opaque: u32) -> MemResult<()> {
+    let header = PacketHeader::response(opcode, status, 0, 0, value.len() as u32, opaque);
+    socket.write_all(&header).await?;
+    socket.write_all(value).await?;
+    Ok(())
+}
+
+/// Write a successful `Get`/`GetQ` response. The wire format puts a 4-byte
+/// flags field in the extras ahead of the value; real-world memcached
+/// clients expect it to be present (even if unused) to parse the body.
+async fn write_get_response(socket: &mut TcpStream, opcode: u8, value: &[u8], opaque: u32) -> MemResult<()> {
+    const FLAGS: [u8; 4] = [0; 4];
+    let header = PacketHeader::response(opcode, Status::Ok, FLAGS.len() as u8, 0, value.len() as u32, opaque);
+    socket.write_all(&header).await?;
+    socket.write_all(&FLAGS).await?;
+    socket.write_all(value).await?;
+    Ok(())
+}
+
+/// A client for a [`MemcachedServer`] (or any memcached-compatible server),
+/// implementing [`CacheBackend`] over a single TCP connection.
+pub struct MemcachedClient {
+    socket: Mutex<TcpStream>,
+}
+
+impl MemcachedClient {
+    /// Connect to a memcached-protocol server at `addr`.
+    pub async fn connect(addr: &str) -> MemResult<Self> {
+        let socket = TcpStream::connect(addr).await?;
+        Ok(Self {
+            socket: Mutex::new(socket),
+        })
+    }
+
+    fn key_bytes(key: u64) -> Vec<u8> {
+        key.to_be_bytes().to_vec()
+    }
+
+    async fn send(socket: &mut TcpStream, opcode: Opcode, key: &[u8], extras: &[u8], value: &[u8]) -> MemResult<()> {
+        let mut header = [0u8; HEADER_LEN];
+        header[0] = REQUEST_MAGIC;
+        header[1] = opcode as u8;
+        header[2..4].copy_from_slice(&(key.len() as u16).to_be_bytes());
+        header[4] = extras.len() as u8;
+        let total_body_len = extras.len() + key.len() + value.len();
+        header[8..12].copy_from_slice(&(total_body_len as u32).to_be_bytes());
+
+        socket.write_all(&header).await?;
+        socket.write_all(extras).await?;
+        socket.write_all(key).await?;
+        socket.write_all(value).await?;
+        Ok(())
+    }
+
+    async fn recv(socket: &mut TcpStream) -> MemResult<(Status, Vec<u8>)> {
+        let mut header_buf = [0u8; HEADER_LEN];
+        socket.read_exact(&mut header_buf).await?;
+        let header = PacketHeader::parse(&header_buf);
+        header.check_body_bounds()?;
+        let mut body = vec![0u8; header.total_body_len as usize];
+        socket.read_exact(&mut body).await?;
+        let status = match header.status_or_vbucket {
+            0x0000 => Status::Ok,
+            0x0001 => Status::KeyNotFound,
+            0x0002 => Status::KeyExists,
+            other => return Err(MemError::InvalidLayout(format!("unknown status {other:#x}"))),
+        };
+        let value_start = header.extras_len as usize + header.key_len as usize;
+        Ok((status, body[value_start..].to_vec()))
+    }
+}
+
+#[async_trait]
+impl CacheBackend for MemcachedClient {
+    async fn get(&self, key: u64) -> MemResult<Option<Vec<u8>>> {
+        let mut socket = self.socket.lock().await;
+        Self::send(&mut socket, Opcode::Get, &Self::key_bytes(key), &[], &[]).await?;
+        let (status, value) = Self::recv(&mut socket).await?;
+        match status {
+            Status::Ok => Ok(Some(value)),
+            Status::KeyNotFound => Ok(None),
+            Status::KeyExists => Err(MemError::Storage("unexpected status for get".into())),
+        }
+    }
+
+    async fn set(&self, key: u64, value: Vec<u8>, ttl: Duration) -> MemResult<()> {
+        let mut extras = [0u8; 8]; // flags=0, ttl=seconds
+        extras[4..8].copy_from_slice(&(ttl.as_secs() as u32).to_be_bytes());
+        let mut socket = self.socket.lock().await;
+        Self::send(&mut socket, Opcode::Set, &Self::key_bytes(key), &extras, &value).await?;
+        let (status, _) = Self::recv(&mut socket).await?;
+        match status {
+            Status::Ok => Ok(()),
+            other => Err(MemError::Storage(format!("set failed: {other:?}"))),
+        }
+    }
+
+    async fn delete(&self, key: u64) -> MemResult<()> {
+        let mut socket = self.socket.lock().await;
+        Self::send(&mut socket, Opcode::Delete, &Self::key_bytes(key), &[], &[]).await?;
+        let (status, _) = Self::recv(&mut socket).await?;
+        match status {
+            Status::Ok | Status::KeyNotFound => Ok(()),
+            other => Err(MemError::Storage(format!("delete failed: {other:?}"))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn spawn_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = Arc::new(MemcachedServer::new());
+        tokio::spawn(server.serve(listener));
+        addr.to_string()
+    }
+
+    #[tokio::test]
+    async fn set_then_get_roundtrips_over_the_wire() {
+        let addr = spawn_server().await;
+        let client = MemcachedClient::connect(&addr).await.unwrap();
+
+        client.set(42, vec![9, 9, 9], Duration::from_secs(60)).await.unwrap();
+        assert_eq!(client.get(42).await.unwrap(), Some(vec![9, 9, 9]));
+    }
+
+    #[tokio::test]
+    async fn get_miss_returns_none() {
+        let addr = spawn_server().await;
+        let client = MemcachedClient::connect(&addr).await.unwrap();
+        assert_eq!(client.get(1).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn delete_removes_value() {
+        let addr = spawn_server().await;
+        let client = MemcachedClient::connect(&addr).await.unwrap();
+        client.set(7, vec![1], Duration::from_secs(60)).await.unwrap();
+        client.delete(7).await.unwrap();
+        assert_eq!(client.get(7).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn get_response_carries_flags_extras_on_the_wire() {
+        let addr = spawn_server().await;
+        let client = MemcachedClient::connect(&addr).await.unwrap();
+        client.set(1, vec![7, 7], Duration::from_secs(60)).await.unwrap();
+
+        let mut socket = client.socket.lock().await;
+        MemcachedClient::send(&mut socket, Opcode::Get, &1u64.to_be_bytes(), &[], &[]).await.unwrap();
+        let mut header_buf = [0u8; HEADER_LEN];
+        socket.read_exact(&mut header_buf).await.unwrap();
+        let header = PacketHeader::parse(&header_buf);
+        assert_eq!(header.extras_len, 4, "Get hit must carry a 4-byte flags extras field");
+    }
+
+    #[tokio::test]
+    async fn malformed_header_closes_the_connection() {
+        let addr = spawn_server().await;
+        let mut socket = TcpStream::connect(&addr).await.unwrap();
+
+        // extras_len + key_len (300) exceeds total_body_len (10): invalid.
+        let mut header = [0u8; HEADER_LEN];
+        header[0] = REQUEST_MAGIC;
+        header[1] = Opcode::Get as u8;
+        header[2..4].copy_from_slice(&300u16.to_be_bytes()); // key_len
+        header[8..12].copy_from_slice(&10u32.to_be_bytes()); // total_body_len
+        socket.write_all(&header).await.unwrap();
+
+        let mut buf = [0u8; 1];
+        let n = socket.read(&mut buf).await.unwrap_or(0);
+        assert_eq!(n, 0, "server should close the connection on a malformed header");
+    }
+
+    #[tokio::test]
+    async fn client_rejects_malformed_response_header_instead_of_panicking() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (mut peer, _) = listener.accept().await.unwrap();
+            // Read the client's request header, then reply with a header claiming
+            // key_len = 300 while total_body_len = 4: invalid.
+            let mut req_header = [0u8; HEADER_LEN];
+            peer.read_exact(&mut req_header).await.unwrap();
+
+            let mut bad_response = [0u8; HEADER_LEN];
+            bad_response[0] = RESPONSE_MAGIC;
+            bad_response[2..4].copy_from_slice(&300u16.to_be_bytes()); // key_len
+            bad_response[8..12].copy_from_slice(&4u32.to_be_bytes()); // total_body_len
+            peer.write_all(&bad_response).await.unwrap();
+            peer.write_all(&[0u8; 4]).await.unwrap(); // satisfies total_body_len
+        });
+
+        let client = MemcachedClient::connect(&addr.to_string()).await.unwrap();
+        let err = client.get(1).await.unwrap_err();
+        assert!(matches!(err, MemError::InvalidLayout(_)));
+    }
+}
@@ -0,0 +1,78 @@
+//! Pluggable caching for embeddings and retrieval results.
+//!
+//! [`CacheBackend`] abstracts over where cached values actually live: the
+//! [`in_process`] implementation keeps a single process's hits in memory,
+//! while [`memcached`] speaks the memcached binary protocol so a fleet of
+//! ReasonKit workers can share one cache tier instead of each re-embedding
+//! the same text.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::MemResult;
+
+/// In-process, single-instance cache.
+pub mod in_process;
+
+/// Memcached binary-protocol client and server.
+pub mod memcached;
+
+/// A cache tier for embedding vectors and retrieval results.
+///
+/// Values are opaque bytes: callers are responsible for (de)serializing
+/// `Vec<f32>` embeddings or ranked hit lists before calling in.
+#[async_trait]
+pub trait CacheBackend: Send + Sync {
+    /// Fetch the raw bytes stored for `key`, if present and not expired.
+    async fn get(&self, key: u64) -> MemResult<Option<Vec<u8>>>;
+
+    /// Store `value` for `key` with the given time-to-live.
+    async fn set(&self, key: u64, value: Vec<u8>, ttl: Duration) -> MemResult<()>;
+
+    /// Remove any value stored for `key`.
+    async fn delete(&self, key: u64) -> MemResult<()>;
+}
+
+/// Cache key for an embedding of `text` under `model_id`.
+///
+/// Text is normalized (trimmed, lowercased) before hashing so trivial
+/// whitespace/casing differences share a cache entry.
+pub fn embedding_cache_key(model_id: &str, text: &str) -> u64 {
+    let normalized = text.trim().to_lowercase();
+    let mut hasher = DefaultHasher::new();
+    model_id.hash(&mut hasher);
+    normalized.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Cache key for a retrieval result set for `query` under `retriever_config`.
+pub fn retrieval_cache_key(query: &str, top_k: usize, retriever_config: &str) -> u64 {
+    let normalized = query.trim().to_lowercase();
+    let mut hasher = DefaultHasher::new();
+    normalized.hash(&mut hasher);
+    top_k.hash(&mut hasher);
+    retriever_config.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn embedding_key_normalizes_text() {
+        let a = embedding_cache_key("bge-m3", "Hello World");
+        let b = embedding_cache_key("bge-m3", "  hello world  ");
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn retrieval_key_distinguishes_config() {
+        let a = retrieval_cache_key("q", 10, "rrf");
+        let b = retrieval_cache_key("q", 10, "rerank");
+        assert_ne!(a, b);
+    }
+}
@@ -0,0 +1,28 @@
+//! Dense vector embedding services.
+//!
+//! Supports local embeddings (BGE-M3 via ONNX) and remote providers (OpenAI,
+//! Anthropic, etc.) behind a single [`EmbeddingService`] trait, with caching
+//! and batching so repeated text doesn't re-hit a model.
+
+use async_trait::async_trait;
+
+use crate::MemResult;
+
+/// Embedding result and query caching.
+pub mod cache;
+
+/// A service that turns text into dense vectors.
+#[async_trait]
+pub trait EmbeddingService: Send + Sync {
+    /// Identifier for the underlying model, used for cache keying.
+    fn model_id(&self) -> &str;
+
+    /// Dimension of vectors produced by this service.
+    fn dim(&self) -> usize;
+
+    /// Embed a single piece of text.
+    async fn embed_one(&self, text: &str) -> MemResult<Vec<f32>>;
+
+    /// Embed a batch of texts, preserving input order.
+    async fn embed_batch(&self, texts: &[&str]) -> MemResult<Vec<Vec<f32>>>;
+}
@@ -0,0 +1,34 @@
+//! Error types shared across all `reasonkit-mem` modules.
+
+use thiserror::Error;
+
+/// Result alias used throughout this crate.
+pub type MemResult<T> = std::result::Result<T, MemError>;
+
+/// Top-level error type for memory and retrieval operations.
+#[derive(Debug, Error)]
+pub enum MemError {
+    /// A storage backend failed to read or write data.
+    #[error("storage error: {0}")]
+    Storage(String),
+
+    /// An embedding provider failed to produce a vector.
+    #[error("embedding error: {0}")]
+    Embedding(String),
+
+    /// A retrieval or fusion step failed.
+    #[error("retrieval error: {0}")]
+    Retrieval(String),
+
+    /// The on-disk or wire representation of a record was malformed.
+    #[error("invalid data layout: {0}")]
+    InvalidLayout(String),
+
+    /// An I/O operation failed.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    /// Configuration was missing or inconsistent.
+    #[error("configuration error: {0}")]
+    Config(String),
+}
@@ -0,0 +1,9 @@
+//! BM25/Tantivy sparse indexing.
+//!
+//! Provides full-text search indexing, custom analyzers, and incremental
+//! updates on top of Tantivy's BM25 implementation, plus the [`substring`]
+//! prefilter used to turn ranked BM25 hits into exact-phrase matches and
+//! highlighted snippets.
+
+/// Fast substring search for exact-phrase filtering and snippet highlighting.
+pub mod substring;
@@ -0,0 +1,246 @@
+//! `memchr`-style substring prefilter.
+//!
+//! BM25 retrieval narrows a corpus down to a handful of candidate documents,
+//! but confirming an exact phrase match (or locating spans to highlight in a
+//! snippet) still means scanning each document's body. A naive `O(n*m)` scan
+//! over long documents is wasteful during reranking and context assembly, so
+//! [`Finder`] instead:
+//!
+//! 1. Picks the statistically rarest byte in the needle (and, for longer
+//!    needles, a second rare byte at a different offset) using a
+//!    precomputed byte-frequency table.
+//! 2. Scans the haystack for *only* that rare byte using `memchr`/`memchr2`,
+//!    which is SIMD-accelerated where available and falls back to a
+//!    SWAR (SIMD-within-a-register) word-at-a-time scan otherwise.
+//! 3. Verifies a full byte comparison only at the handful of candidate
+//!    offsets produced by step 2, anchored so the rare byte lines up with
+//!    its position in the needle.
+//!
+//! This keeps the common case - the rare byte almost never occurs - close
+//! to `O(n)` instead of `O(n*m)`.
+
+use memchr::{memchr, memchr2};
+
+/// Rough relative byte frequencies over a typical UTF-8 text corpus, lower
+/// is rarer. Mirrors the heuristic used by literal prefilters in regex
+/// engines: common ASCII letters and whitespace rank high, rare symbols and
+/// most non-ASCII continuation bytes rank low.
+#[rustfmt::skip]
+const BYTE_FREQUENCY: [u8; 256] = [
+    // 0x00..=0x1F: control characters, rare in text bodies.
+    0,1,1,1,1,1,1,1,1,4,6,1,1,2,1,1,
+    1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,
+    // 0x20..=0x3F: space/punctuation/digits, common.
+    9,3,3,2,2,1,2,3,3,3,2,3,5,3,6,3,
+    5,5,5,5,4,4,4,4,4,4,3,3,2,2,2,2,
+    // 0x40..=0x5F: '@', uppercase letters, brackets.
+    2,6,5,6,6,7,5,4,5,7,3,3,5,5,6,6,
+    5,3,6,6,6,5,4,4,3,4,3,2,1,2,1,2,
+    // 0x60..=0x7F: backtick, lowercase letters, braces.
+    1,9,7,7,8,9,6,6,6,9,5,5,7,7,9,8,
+    6,3,8,8,8,7,6,6,5,6,4,2,1,2,1,1,
+    // 0x80..=0xFF: non-ASCII / UTF-8 continuation bytes, rare overall.
+    1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,
+    1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,
+    1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,
+    1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,
+    1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,
+    1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,
+    1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,
+    1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,
+];
+
+fn frequency(byte: u8) -> u8 {
+    BYTE_FREQUENCY[byte as usize]
+}
+
+enum RareBytes {
+    One { byte: u8, offset: usize },
+    Two { byte1: u8, offset1: usize, byte2: u8, offset2: usize },
+}
+
+/// A reusable substring finder, built once per needle and then run against
+/// any number of haystacks.
+pub struct Finder {
+    needle: Vec<u8>,
+    rare: RareBytes,
+}
+
+impl Finder {
+    /// Build a finder for `needle`. Panics if `needle` is empty.
+    pub fn new(needle: &[u8]) -> Self {
+        assert!(!needle.is_empty(), "Finder needle must be non-empty");
+
+        let mut offset1 = 0usize;
+        for (i, &b) in needle.iter().enumerate() {
+            if frequency(b) < frequency(needle[offset1]) {
+                offset1 = i;
+            }
+        }
+        let byte1 = needle[offset1];
+
+        let rare = if needle.len() == 1 {
+            RareBytes::One { byte: byte1, offset: offset1 }
+        } else {
+            // Pick the rarest byte at a *different* offset so the pair
+            // narrows candidates more than scanning for `byte1` twice.
+            let mut offset2 = if offset1 == 0 { 1 } else { 0 };
+            for (i, &b) in needle.iter().enumerate() {
+                if i != offset1 && frequency(b) < frequency(needle[offset2]) {
+                    offset2 = i;
+                }
+            }
+            RareBytes::Two {
+                byte1,
+                offset1,
+                byte2: needle[offset2],
+                offset2,
+            }
+        };
+
+        Self { needle: needle.to_vec(), rare }
+    }
+
+    /// Iterate over all non-overlapping match start offsets in `haystack`.
+    pub fn find_iter<'f, 'h>(&'f self, haystack: &'h [u8]) -> FindIter<'f, 'h> {
+        FindIter { finder: self, haystack, pos: 0 }
+    }
+
+    /// Find the first match at or after byte offset `from`.
+    pub fn find_at(&self, haystack: &[u8], from: usize) -> Option<usize> {
+        if from > haystack.len() || self.needle.len() > haystack.len() {
+            return None;
+        }
+
+        let mut search_from = from;
+        loop {
+            let rare_hit = match &self.rare {
+                RareBytes::One { byte, .. } => memchr(*byte, &haystack[search_from..]),
+                RareBytes::Two { byte1, byte2, .. } => memchr2(*byte1, *byte2, &haystack[search_from..]),
+            }?;
+            let rare_pos = search_from + rare_hit;
+
+            // Anchor the candidate start so the rare byte we actually hit
+            // lines up with whichever offset it occupies in the needle.
+            let matched_byte = haystack[rare_pos];
+            let offset = match &self.rare {
+                RareBytes::One { offset, .. } => *offset,
+                RareBytes::Two { byte1, offset1, offset2, .. } => {
+                    if matched_byte == *byte1 { *offset1 } else { *offset2 }
+                }
+            };
+
+            if let Some(candidate) = rare_pos.checked_sub(offset) {
+                // A candidate anchored before `from` would either fall
+                // outside the caller's requested range or, for a
+                // self-overlapping needle, re-match bytes already claimed
+                // by a previous `find_iter` hit. Reject it and keep
+                // scanning rather than returning an overlapping match.
+                if candidate >= from
+                    && candidate + self.needle.len() <= haystack.len()
+                    && haystack[candidate..candidate + self.needle.len()] == self.needle[..]
+                {
+                    return Some(candidate);
+                }
+            }
+            search_from = rare_pos + 1;
+        }
+    }
+}
+
+/// Iterator over non-overlapping matches produced by [`Finder::find_iter`].
+pub struct FindIter<'f, 'h> {
+    finder: &'f Finder,
+    haystack: &'h [u8],
+    pos: usize,
+}
+
+impl Iterator for FindIter<'_, '_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let found = self.finder.find_at(self.haystack, self.pos)?;
+        self.pos = found + self.finder.needle.len().max(1);
+        Some(found)
+    }
+}
+
+/// Locate every match of `needle` in `text` and return `(start, end)` byte
+/// spans, e.g. for highlighting a snippet around an exact-phrase hit.
+pub fn highlight_spans(text: &str, needle: &str) -> Vec<(usize, usize)> {
+    if needle.is_empty() {
+        return Vec::new();
+    }
+    Finder::new(needle.as_bytes())
+        .find_iter(text.as_bytes())
+        .map(|start| (start, start + needle.len()))
+        .collect()
+}
+
+/// Whether `needle` occurs anywhere in `haystack`, for post-filtering BM25
+/// hits down to exact-phrase matches.
+pub fn contains(haystack: &str, needle: &str) -> bool {
+    if needle.is_empty() {
+        return true;
+    }
+    Finder::new(needle.as_bytes()).find_at(haystack.as_bytes(), 0).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_single_match() {
+        let finder = Finder::new(b"needle");
+        let hits: Vec<_> = finder.find_iter(b"hay hay needle hay").collect();
+        assert_eq!(hits, vec![8]);
+    }
+
+    #[test]
+    fn finds_multiple_non_overlapping_matches() {
+        let finder = Finder::new(b"ab");
+        let hits: Vec<_> = finder.find_iter(b"abXabXab").collect();
+        assert_eq!(hits, vec![0, 3, 6]);
+    }
+
+    #[test]
+    fn no_match_returns_empty() {
+        let finder = Finder::new(b"zzz");
+        assert_eq!(finder.find_iter(b"no such substring here").count(), 0);
+    }
+
+    #[test]
+    fn single_byte_needle() {
+        let finder = Finder::new(b"q");
+        let hits: Vec<_> = finder.find_iter(b"quick query").collect();
+        assert_eq!(hits, vec![0, 6]);
+    }
+
+    #[test]
+    fn self_overlapping_needle_does_not_yield_overlapping_matches() {
+        let finder = Finder::new(b"aba");
+        let hits: Vec<_> = finder.find_iter(b"ababa").collect();
+        // "ababa" contains overlapping occurrences of "aba" at 0 and 2, but
+        // find_iter's matches must not share bytes.
+        assert_eq!(hits, vec![0]);
+    }
+
+    #[test]
+    fn find_at_never_returns_before_from() {
+        let finder = Finder::new(b"ab");
+        assert_eq!(finder.find_at(b"Xaby", 2), None);
+    }
+
+    #[test]
+    fn highlight_spans_cover_the_match() {
+        let spans = highlight_spans("the quick brown fox", "quick");
+        assert_eq!(spans, vec![(4, 9)]);
+    }
+
+    #[test]
+    fn contains_matches_exact_phrase() {
+        assert!(contains("retrieval augmented generation", "augmented generation"));
+        assert!(!contains("retrieval augmented generation", "augmented retrieval"));
+    }
+}
@@ -0,0 +1,418 @@
+//! Packed, memory-mappable storage backend (flatdata-style) for chunks and
+//! their dense embeddings.
+//!
+//! A store is a single file with three segments:
+//!
+//! ```text
+//! +----------------+------------------------+------------------------+
+//! | Header (fixed) | Vector block (f32 x N) | Blob region            |
+//! +----------------+------------------------+------------------------+
+//! ```
+//!
+//! - **Header** carries the chunk count, embedding dimension, and the byte
+//!   offsets of the other two segments.
+//! - **Vector block** is a contiguous run of `f32`, row-major by chunk id
+//!   (`row = chunk_id * dim`), giving O(1) vector fetch with no parsing.
+//! - **Blob region** starts with an offset table of one (`u64` offset, `u32`
+//!   length) little-endian entry per chunk, followed by one record per chunk
+//!   - `document_id` followed by UTF-8 text - so records can vary in size.
+//!
+//! [`MmapStoreBuilder`] appends records in id order and writes the finished
+//! file; [`MmapStore`] maps that file and serves `&[f32]` and `&str` slices
+//! directly out of the page cache without deserializing.
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+
+use async_trait::async_trait;
+use memmap2::Mmap;
+
+use crate::types::{Chunk, ChunkId, DocumentId};
+use crate::{MemError, MemResult};
+
+use super::Storage;
+
+const MAGIC: &[u8; 8] = b"RKMMAP1\0";
+const HEADER_LEN: usize = 8 + 4 + 8 + 4 + 8 + 8;
+
+struct Header {
+    chunk_count: u64,
+    dim: u32,
+    vector_block_offset: u64,
+    blob_table_offset: u64,
+}
+
+impl Header {
+    fn write_to(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&1u32.to_le_bytes());
+        out.extend_from_slice(&self.chunk_count.to_le_bytes());
+        out.extend_from_slice(&self.dim.to_le_bytes());
+        out.extend_from_slice(&self.vector_block_offset.to_le_bytes());
+        out.extend_from_slice(&self.blob_table_offset.to_le_bytes());
+    }
+
+    /// Check that the segment offsets and sizes this header describes
+    /// actually fit within a file of `file_len` bytes, so accessors can
+    /// trust them instead of doing unchecked arithmetic on a stale,
+    /// truncated, or corrupted file.
+    fn validate(&self, file_len: usize) -> MemResult<()> {
+        let vector_block_offset = self.vector_block_offset as usize;
+        let blob_table_offset = self.blob_table_offset as usize;
+        if vector_block_offset > file_len || blob_table_offset > file_len {
+            return Err(MemError::InvalidLayout(
+                "segment offset beyond end of file".into(),
+            ));
+        }
+
+        let overflow = || MemError::InvalidLayout("segment size overflows usize".into());
+        let vector_block_len = (self.chunk_count as usize)
+            .checked_mul(self.dim as usize)
+            .and_then(|n| n.checked_mul(std::mem::size_of::<f32>()))
+            .ok_or_else(overflow)?;
+        let vector_block_end = vector_block_offset
+            .checked_add(vector_block_len)
+            .ok_or_else(overflow)?;
+        if vector_block_end > blob_table_offset || vector_block_end > file_len {
+            return Err(MemError::InvalidLayout(
+                "vector block overruns the blob table".into(),
+            ));
+        }
+
+        let blob_table_len = (self.chunk_count as usize).checked_mul(12).ok_or_else(overflow)?;
+        let blob_table_end = blob_table_offset.checked_add(blob_table_len).ok_or_else(overflow)?;
+        if blob_table_end > file_len {
+            return Err(MemError::InvalidLayout(
+                "blob table overruns end of file".into(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn read_from(bytes: &[u8]) -> MemResult<Self> {
+        if bytes.len() < HEADER_LEN {
+            return Err(MemError::InvalidLayout("file shorter than header".into()));
+        }
+        if &bytes[0..8] != MAGIC {
+            return Err(MemError::InvalidLayout("bad magic".into()));
+        }
+        let mut off = 12; // skip magic + version
+        let chunk_count = u64::from_le_bytes(bytes[off..off + 8].try_into().unwrap());
+        off += 8;
+        let dim = u32::from_le_bytes(bytes[off..off + 4].try_into().unwrap());
+        off += 4;
+        let vector_block_offset = u64::from_le_bytes(bytes[off..off + 8].try_into().unwrap());
+        off += 8;
+        let blob_table_offset = u64::from_le_bytes(bytes[off..off + 8].try_into().unwrap());
+        Ok(Header {
+            chunk_count,
+            dim,
+            vector_block_offset,
+            blob_table_offset,
+        })
+    }
+}
+
+/// Appends chunk records and writes a packed [`MmapStore`] file.
+///
+/// Records must be pushed in ascending, contiguous `chunk_id` order starting
+/// at zero; `MmapStore` indexes by row position, not by a sparse key.
+pub struct MmapStoreBuilder {
+    dim: usize,
+    vectors: Vec<f32>,
+    blobs: Vec<u8>,
+    blob_table: Vec<(u64, u32)>,
+}
+
+impl MmapStoreBuilder {
+    /// Create a builder for vectors of the given dimension.
+    pub fn new(dim: usize) -> Self {
+        Self {
+            dim,
+            vectors: Vec::new(),
+            blobs: Vec::new(),
+            blob_table: Vec::new(),
+        }
+    }
+
+    /// Append one chunk. `id` must equal the number of records pushed so far.
+    pub fn push(&mut self, id: ChunkId, document_id: DocumentId, text: &str, vector: &[f32]) -> MemResult<()> {
+        if id != self.blob_table.len() as u64 {
+            return Err(MemError::InvalidLayout(format!(
+                "chunks must be pushed in order: expected id {}, got {id}",
+                self.blob_table.len()
+            )));
+        }
+        if vector.len() != self.dim {
+            return Err(MemError::InvalidLayout(format!(
+                "expected vector of dim {}, got {}",
+                self.dim,
+                vector.len()
+            )));
+        }
+        self.vectors.extend_from_slice(vector);
+
+        let offset = self.blobs.len() as u64;
+        let record_len = 8 + text.len();
+        self.blobs.extend_from_slice(&document_id.to_le_bytes());
+        self.blobs.extend_from_slice(text.as_bytes());
+        self.blob_table.push((offset, record_len as u32));
+
+        Ok(())
+    }
+
+    /// Serialize the builder's contents to `path`.
+    pub fn build(self, path: impl AsRef<Path>) -> MemResult<()> {
+        let chunk_count = self.blob_table.len() as u64;
+        let vector_block_offset = HEADER_LEN as u64;
+        let vector_block_len = self.vectors.len() * std::mem::size_of::<f32>();
+        let blob_table_offset = vector_block_offset + vector_block_len as u64;
+
+        let mut out = Vec::with_capacity(
+            HEADER_LEN + vector_block_len + self.blob_table.len() * 12 + self.blobs.len(),
+        );
+
+        Header {
+            chunk_count,
+            dim: self.dim as u32,
+            vector_block_offset,
+            blob_table_offset,
+        }
+        .write_to(&mut out);
+
+        for v in &self.vectors {
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        for (offset, len) in &self.blob_table {
+            out.extend_from_slice(&offset.to_le_bytes());
+            out.extend_from_slice(&len.to_le_bytes());
+        }
+        out.extend_from_slice(&self.blobs);
+
+        let mut file = File::create(path)?;
+        file.write_all(&out)?;
+        Ok(())
+    }
+}
+
+/// A memory-mapped, read-only view over a file written by
+/// [`MmapStoreBuilder`]. Vector and text lookups are zero-copy: they return
+/// slices into the mapped file rather than allocating.
+pub struct MmapStore {
+    mmap: Mmap,
+    dim: usize,
+    chunk_count: usize,
+    blob_table_offset: usize,
+    vector_block_offset: usize,
+}
+
+impl MmapStore {
+    /// Map `path` into memory and validate its header.
+    pub fn open(path: impl AsRef<Path>) -> MemResult<Self> {
+        let file = File::open(path)?;
+        // SAFETY: the backing file is not concurrently truncated by this
+        // process; callers must not mutate the file while it is mapped.
+        let mmap = unsafe { Mmap::map(&file)? };
+        let header = Header::read_from(&mmap)?;
+        header.validate(mmap.len())?;
+        Ok(Self {
+            mmap,
+            dim: header.dim as usize,
+            chunk_count: header.chunk_count as usize,
+            blob_table_offset: header.blob_table_offset as usize,
+            vector_block_offset: header.vector_block_offset as usize,
+        })
+    }
+
+    /// Number of chunks in this store.
+    pub fn chunk_count(&self) -> usize {
+        self.chunk_count
+    }
+
+    /// Embedding dimension of every vector in this store.
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    /// Fetch the dense embedding for `id` with no copy or deserialization.
+    ///
+    /// Returns `None` for an out-of-range id as well as for an otherwise
+    /// in-range id whose computed byte range doesn't fit the mapped file -
+    /// the latter can only happen against a corrupted or truncated store,
+    /// since `open` already validated the header-derived segment bounds.
+    pub fn vector(&self, id: ChunkId) -> Option<&[f32]> {
+        let id = id as usize;
+        if id >= self.chunk_count {
+            return None;
+        }
+        let row_bytes = self.dim.checked_mul(std::mem::size_of::<f32>())?;
+        let start = self.vector_block_offset.checked_add(id.checked_mul(row_bytes)?)?;
+        let end = start.checked_add(row_bytes)?;
+        let bytes = self.mmap.get(start..end)?;
+        // Vectors are written as little-endian f32 arrays, so a byte cast is
+        // sound whenever the host is little-endian; this mirrors the
+        // `to_le_bytes` write path above.
+        Some(bytemuck::cast_slice(bytes))
+    }
+
+    fn blob_entry(&self, id: ChunkId) -> Option<(u64, u32)> {
+        let id = id as usize;
+        if id >= self.chunk_count {
+            return None;
+        }
+        let entry_off = self.blob_table_offset.checked_add(id.checked_mul(12)?)?;
+        let bytes = self.mmap.get(entry_off..entry_off.checked_add(12)?)?;
+        let offset = u64::from_le_bytes(bytes[0..8].try_into().unwrap());
+        let len = u32::from_le_bytes(bytes[8..12].try_into().unwrap());
+        Some((offset, len))
+    }
+
+    fn blob_region_offset(&self) -> Option<usize> {
+        self.blob_table_offset.checked_add(self.chunk_count.checked_mul(12)?)
+    }
+
+    /// Fetch the source document id for `id` without copying its text.
+    pub fn document_id(&self, id: ChunkId) -> Option<DocumentId> {
+        let (offset, _) = self.blob_entry(id)?;
+        let start = self.blob_region_offset()?.checked_add(offset as usize)?;
+        let bytes = self.mmap.get(start..start.checked_add(8)?)?;
+        Some(u64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    /// Fetch the chunk text for `id` with no copy or deserialization.
+    ///
+    /// A record's stored length must be at least the 8-byte `document_id`
+    /// prefix; a shorter length means the record (or the file it came from)
+    /// is corrupt, so this returns `None` rather than underflowing.
+    pub fn text(&self, id: ChunkId) -> Option<&str> {
+        let (offset, len) = self.blob_entry(id)?;
+        let text_len = (len as usize).checked_sub(8)?;
+        let start = self.blob_region_offset()?.checked_add(offset as usize)?.checked_add(8)?;
+        let end = start.checked_add(text_len)?;
+        let bytes = self.mmap.get(start..end)?;
+        std::str::from_utf8(bytes).ok()
+    }
+}
+
+#[async_trait]
+impl Storage for MmapStore {
+    async fn get_chunk(&self, id: ChunkId) -> MemResult<Option<Chunk>> {
+        let Some(text) = self.text(id) else {
+            return Ok(None);
+        };
+        let document_id = self.document_id(id).unwrap_or_default();
+        let embedding = self.vector(id).map(|v| v.to_vec());
+        Ok(Some(Chunk {
+            id,
+            document_id,
+            text: text.to_string(),
+            embedding,
+        }))
+    }
+
+    async fn get_vector(&self, id: ChunkId) -> MemResult<Option<&[f32]>> {
+        Ok(self.vector(id))
+    }
+
+    fn len(&self) -> usize {
+        self.chunk_count
+    }
+}
+
+/// Remove a store file and any sibling files it may have created.
+pub fn remove_store(path: impl AsRef<Path>) -> MemResult<()> {
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip(dim: usize, records: &[(DocumentId, &str, Vec<f32>)]) -> MmapStore {
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!("reasonkit_mmap_test_{}.bin", std::process::id()));
+
+        let mut builder = MmapStoreBuilder::new(dim);
+        for (i, (doc_id, text, vector)) in records.iter().enumerate() {
+            builder.push(i as u64, *doc_id, text, vector).unwrap();
+        }
+        builder.build(&path).unwrap();
+
+        let store = MmapStore::open(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        store
+    }
+
+    #[test]
+    fn roundtrips_vectors_and_text() {
+        let store = roundtrip(
+            3,
+            &[
+                (1, "first chunk", vec![0.1, 0.2, 0.3]),
+                (1, "second, slightly longer chunk", vec![0.4, 0.5, 0.6]),
+                (2, "third", vec![0.7, 0.8, 0.9]),
+            ],
+        );
+
+        assert_eq!(store.chunk_count(), 3);
+        assert_eq!(store.vector(0), Some(&[0.1, 0.2, 0.3][..]));
+        assert_eq!(store.vector(2), Some(&[0.7, 0.8, 0.9][..]));
+        assert_eq!(store.text(1), Some("second, slightly longer chunk"));
+        assert_eq!(store.document_id(2), Some(2));
+        assert_eq!(store.vector(3), None);
+    }
+
+    #[test]
+    fn rejects_out_of_order_ids() {
+        let mut builder = MmapStoreBuilder::new(2);
+        builder.push(0, 1, "ok", &[0.0, 0.0]).unwrap();
+        let err = builder.push(2, 1, "bad", &[0.0, 0.0]).unwrap_err();
+        assert!(matches!(err, MemError::InvalidLayout(_)));
+    }
+
+    #[test]
+    fn corrupted_blob_length_returns_none_instead_of_panicking() {
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!("reasonkit_mmap_corrupt_test_{}.bin", std::process::id()));
+
+        let mut builder = MmapStoreBuilder::new(2);
+        builder.push(0, 1, "hello", &[0.1, 0.2]).unwrap();
+        builder.build(&path).unwrap();
+
+        // Flip the first blob entry's length to 3 - shorter than the 8-byte
+        // document_id prefix every record must have.
+        let mut bytes = fs::read(&path).unwrap();
+        let vector_block_len = 2 * std::mem::size_of::<f32>();
+        let len_field_offset = HEADER_LEN + vector_block_len + 8;
+        bytes[len_field_offset..len_field_offset + 4].copy_from_slice(&3u32.to_le_bytes());
+        fs::write(&path, &bytes).unwrap();
+
+        let store = MmapStore::open(&path).unwrap();
+        assert_eq!(store.text(0), None);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn rejects_truncated_file() {
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!("reasonkit_mmap_truncated_test_{}.bin", std::process::id()));
+
+        let mut builder = MmapStoreBuilder::new(2);
+        builder.push(0, 1, "hello", &[0.1, 0.2]).unwrap();
+        builder.build(&path).unwrap();
+
+        // Truncate into the middle of the (12-byte) blob table itself, so
+        // the file is shorter than what the header's own offsets promise.
+        let vector_block_len = 2 * std::mem::size_of::<f32>();
+        let blob_table_offset = HEADER_LEN + vector_block_len;
+        let bytes = fs::read(&path).unwrap();
+        fs::write(&path, &bytes[..blob_table_offset + 6]).unwrap();
+
+        let err = MmapStore::open(&path).unwrap_err();
+        assert!(matches!(err, MemError::InvalidLayout(_)));
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,31 @@
+//! Vector and file-based storage backends.
+//!
+//! Backends implement the [`Storage`] trait so callers can swap between
+//! Qdrant (embedded or clustered), the generic file-based fallback, and the
+//! read-optimized [`mmap`] backend without changing call sites.
+
+use async_trait::async_trait;
+
+use crate::types::{Chunk, ChunkId};
+use crate::MemResult;
+
+/// Memory-mappable, zero-copy storage backend.
+pub mod mmap;
+
+/// A storage backend for chunks and their dense embeddings.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    /// Fetch a chunk's text and metadata by id.
+    async fn get_chunk(&self, id: ChunkId) -> MemResult<Option<Chunk>>;
+
+    /// Fetch a chunk's dense embedding by id without deserializing text.
+    async fn get_vector(&self, id: ChunkId) -> MemResult<Option<&[f32]>>;
+
+    /// Number of chunks held by this backend.
+    fn len(&self) -> usize;
+
+    /// Whether this backend holds no chunks.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
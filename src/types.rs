@@ -0,0 +1,33 @@
+//! Core types shared across storage, embedding, retrieval, and indexing.
+
+use serde::{Deserialize, Serialize};
+
+/// Unique identifier for a document.
+pub type DocumentId = u64;
+
+/// Unique identifier for a chunk within a document.
+pub type ChunkId = u64;
+
+/// A source document prior to chunking.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Document {
+    /// Unique identifier for this document.
+    pub id: DocumentId,
+    /// Original source path or URI, if known.
+    pub source: Option<String>,
+    /// Raw document text.
+    pub text: String,
+}
+
+/// A retrievable unit of text with an associated dense embedding.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Chunk {
+    /// Unique identifier for this chunk.
+    pub id: ChunkId,
+    /// The document this chunk was derived from.
+    pub document_id: DocumentId,
+    /// Chunk text content.
+    pub text: String,
+    /// Dense embedding vector, if computed.
+    pub embedding: Option<Vec<f32>>,
+}